@@ -1,4 +1,5 @@
 use clap::ArgMatches;
+use serde::Deserialize;
 use std::{
     fs::File,
     path::{Path, PathBuf},
@@ -11,6 +12,44 @@ struct AddList {
     dst: String,
 }
 
+/// Describes a pack project so it can be built from a `vpk.toml` file
+/// instead of repeated CLI flags.
+///
+/// `add` maps each `src` path to its `dst` archive path, exactly like the
+/// repeated `--add src=dst` CLI flag.
+#[derive(Deserialize)]
+struct Manifest {
+    sfo: PathBuf,
+    eboot: PathBuf,
+    vpk: Option<PathBuf>,
+    compress: Option<String>,
+    #[serde(default)]
+    add: std::collections::BTreeMap<String, String>,
+}
+
+impl Manifest {
+    /// Function that reads and parses a manifest file at `path`.
+    ///
+    /// Returns a fully parsed [Manifest] on success, exiting the process on
+    /// read or parse failure.
+    fn read(path: &Path) -> Manifest {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(error) => {
+                println!("error: Unable to read the {:?} manifest file : {:?}", path, error);
+                std::process::exit(exitcode::NOINPUT);
+            }
+        };
+        match toml::from_str(&content) {
+            Ok(manifest) => manifest,
+            Err(error) => {
+                println!("error: Unable to parse the {:?} manifest file : {:?}", path, error);
+                std::process::exit(exitcode::CONFIG);
+            }
+        }
+    }
+}
+
 /// [std::fmt::Debug] implementation for [AddList]
 impl std::fmt::Debug for AddList {
     fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -28,6 +67,8 @@ const DEFAULT_OUTPUT_FILE: &str = "output.vpk";
 const DEFAULT_SFO_VPK_PATH: &str = "sce_sys/param.sfo";
 /// Default EBOOT path in the VPK file
 const DEFAULT_EBOOT_VPK_PATH: &str = "eboot.bin";
+/// Default compression setting, a balanced deflate level
+const DEFAULT_COMPRESSION: &str = "deflate=6";
 
 /// Main function of vita-pack-vpk. Parse all the command line options and
 /// arguments.
@@ -48,7 +89,6 @@ fn main() {
                 .about("Sets the param.sfo file")
                 .validator(check_file)
                 .takes_value(true)
-                .required(true)
                 .display_order(1),
         )
         .arg(
@@ -59,9 +99,18 @@ fn main() {
                 .about("Sets the eboot.bin file")
                 .validator(check_file)
                 .takes_value(true)
-                .required(true)
                 .display_order(2),
         )
+        .arg(
+            Arg::new("manifest")
+                .short('m')
+                .long("manifest")
+                .value_name("vpk.toml")
+                .about("Builds the vpk from a project manifest, CLI flags override its values")
+                .validator(check_file)
+                .takes_value(true)
+                .display_order(7),
+        )
         .arg(
             Arg::new("add")
                 .short('a')
@@ -72,6 +121,32 @@ fn main() {
                 .multiple_occurrences(true)
                 .display_order(3),
         )
+        .arg(
+            Arg::new("list")
+                .short('l')
+                .long("list")
+                .about("Prints the planned archive contents and sizes without writing the vpk")
+                .takes_value(false)
+                .display_order(4),
+        )
+        .arg(
+            Arg::new("compress")
+                .short('c')
+                .long("compress")
+                .value_name("store|deflate|deflate=<0-9>")
+                .about("Sets the compression method used for added entries")
+                .validator(check_compress)
+                .takes_value(true)
+                .default_value(DEFAULT_COMPRESSION)
+                .display_order(5),
+        )
+        .arg(
+            Arg::new("verify")
+                .long("verify")
+                .about("Reopens the produced vpk and validates all required entries")
+                .takes_value(false)
+                .display_order(6),
+        )
         .arg(
             Arg::new("vpk")
                 .about("Name and path to the new .vpk file")
@@ -79,11 +154,87 @@ fn main() {
                 .default_value(DEFAULT_OUTPUT_FILE),
         )
         .get_matches();
-    addlist_vec = build_list(&arg_matches);
-    vpk_path = Path::new(arg_matches.value_of("vpk").unwrap_or_default());
-    match pack_vpk(addlist_vec, vpk_path) {
+
+    let manifest: Option<Manifest> = arg_matches
+        .value_of("manifest")
+        .map(|path| Manifest::read(Path::new(path)));
+
+    addlist_vec = build_list(&arg_matches, manifest.as_ref());
+
+    vpk_path = if arg_matches.occurrences_of("vpk") > 0 {
+        Path::new(arg_matches.value_of("vpk").unwrap())
+    } else if let Some(manifest_vpk) = manifest.as_ref().and_then(|m| m.vpk.as_deref()) {
+        manifest_vpk
+    } else {
+        Path::new(arg_matches.value_of("vpk").unwrap_or(DEFAULT_OUTPUT_FILE))
+    };
+
+    if arg_matches.is_present("list") {
+        print_list(&addlist_vec);
+        return;
+    }
+
+    let compress_str = if arg_matches.occurrences_of("compress") > 0 {
+        arg_matches.value_of("compress").unwrap().to_string()
+    } else if let Some(manifest_compress) = manifest.as_ref().and_then(|m| m.compress.clone()) {
+        // Clap's "compress" validator only runs on the CLI flag, so check
+        // the manifest value the same way here
+        if let Err(error) = check_compress(&manifest_compress) {
+            println!("error: Invalid manifest \"compress\" field: {}", error);
+            std::process::exit(exitcode::CONFIG);
+        }
+        manifest_compress
+    } else {
+        arg_matches
+            .value_of("compress")
+            .unwrap_or(DEFAULT_COMPRESSION)
+            .to_string()
+    };
+    let compression = parse_compress(&compress_str);
+    match pack_vpk(&addlist_vec, vpk_path, compression) {
         Ok(file) => println!("File successfully created [{:?}]", file),
-        Err(error) => println!("Error: {}", error),
+        Err(error) => {
+            println!("Error: {}", error);
+            return;
+        }
+    }
+
+    if arg_matches.is_present("verify") {
+        verify_vpk(&addlist_vec, vpk_path);
+    }
+}
+
+/// Function that prints every [AddList] entry as `dst <- src` along with its
+/// byte size, followed by a human-readable total. Used by the `--list`
+/// dry-run mode so users can confirm the archive layout before packing.
+fn print_list(addlist: &[AddList]) {
+    let mut total: u64 = 0;
+
+    for pair in addlist {
+        let size = std::fs::metadata(&pair.src).map(|m| m.len()).unwrap_or(0);
+        total += size;
+        println!("{} <- {:?} ({} bytes)", pair.dst, pair.src, size);
+    }
+
+    println!("total: {}", human_size(total));
+}
+
+/// Function that formats a byte count as a human-readable size (e.g.
+/// "12.4 MiB").
+fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[unit])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
     }
 }
 
@@ -108,55 +259,301 @@ fn check_add(var: &str) -> Result<(), String> {
     }
 }
 
+/// Function used to check if "--compress" option is correct for Clap
+fn check_compress(var: &str) -> Result<(), String> {
+    match var {
+        "store" | "deflate" => Ok(()),
+        _ if var.starts_with("deflate=") => match var["deflate=".len()..].parse::<i32>() {
+            Ok(level) if (0..=9).contains(&level) => Ok(()),
+            _ => Err(String::from("deflate level must be a number between 0 and 9")),
+        },
+        _ => Err(String::from(
+            "Need <store|deflate|deflate=<0-9>> as compression method",
+        )),
+    }
+}
+
+/// Function that parses a "--compress" value into the [CompressionMethod]
+/// and level to use for [FileOptions].
+///
+/// Returns a tuple of [zip::CompressionMethod] and an optional compression
+/// level.
+fn parse_compress(var: &str) -> (zip::CompressionMethod, Option<i32>) {
+    use zip::CompressionMethod;
+
+    match var {
+        "store" => (CompressionMethod::Stored, None),
+        "deflate" => (CompressionMethod::Deflated, None),
+        _ if var.starts_with("deflate=") => {
+            let level = var["deflate=".len()..].parse::<i32>().unwrap_or(6);
+            (CompressionMethod::Deflated, Some(level))
+        }
+        _ => (CompressionMethod::Deflated, Some(6)),
+    }
+}
+
+#[cfg(test)]
+mod parse_compress_tests {
+    use super::*;
+
+    #[test]
+    fn store_has_no_level() {
+        assert_eq!(parse_compress("store"), (zip::CompressionMethod::Stored, None));
+    }
+
+    #[test]
+    fn deflate_without_level_uses_default() {
+        assert_eq!(
+            parse_compress("deflate"),
+            (zip::CompressionMethod::Deflated, None)
+        );
+    }
+
+    #[test]
+    fn deflate_with_level_is_parsed() {
+        assert_eq!(
+            parse_compress("deflate=9"),
+            (zip::CompressionMethod::Deflated, Some(9))
+        );
+    }
+}
+
 /// Function that will build an [Vec]<[AddList]>. That will set the parsed
 /// options: sfo, eboot and add(s)
 ///
+/// When `manifest` is given, it supplies the sfo, eboot and add entries that
+/// aren't overridden by an explicit CLI flag.
+///
 /// Returns an [Vec]<[AddList]>
-fn build_list(arg_matches: &ArgMatches) -> Vec<AddList> {
-    let sfo_path: &Path;
-    let eboot_path: &Path;
+fn build_list(arg_matches: &ArgMatches, manifest: Option<&Manifest>) -> Vec<AddList> {
+    let sfo_path: PathBuf = resolve_required_path(arg_matches, "sfo", manifest.map(|m| &m.sfo));
+    let eboot_path: PathBuf =
+        resolve_required_path(arg_matches, "eboot", manifest.map(|m| &m.eboot));
     let mut addlist_vec: Vec<AddList>;
 
-    // Get sfo and eboot path from [Clap] arguments matches
-    sfo_path = Path::new(arg_matches.value_of("sfo").unwrap());
-    eboot_path = Path::new(arg_matches.value_of("eboot").unwrap());
-
-    // Create our addlist Vector and push sfo and eboot addlists
+    // Create our addlist Vector and push sfo and eboot addlists, through
+    // push_add_entry so a manifest/CLI "add" entry later mapped to one of
+    // these reserved paths overrides it instead of producing a duplicate
+    // archive entry
     addlist_vec = Vec::new();
-    addlist_vec.push(make_add_list(sfo_path, String::from(DEFAULT_SFO_VPK_PATH)));
-    addlist_vec.push(make_add_list(
-        eboot_path,
-        String::from(DEFAULT_EBOOT_VPK_PATH),
-    ));
+    push_add_entry(
+        &mut addlist_vec,
+        make_add_list(&sfo_path, String::from(DEFAULT_SFO_VPK_PATH)),
+    );
+    push_add_entry(
+        &mut addlist_vec,
+        make_add_list(&eboot_path, String::from(DEFAULT_EBOOT_VPK_PATH)),
+    );
+
+    // Add the manifest's "add" entries first, so CLI "--add" entries below
+    // can override the archive paths it declares
+    if let Some(manifest) = manifest {
+        for (src, dst) in &manifest.add {
+            push_add_entry(&mut addlist_vec, make_add_list(Path::new(src), dst.clone()));
+        }
+    }
 
     // Check if add options are present, parse them, create and addlist and add
-    // them to the AddList Vector
+    // them to the AddList Vector, overriding any manifest entry at the same
+    // destination
     if arg_matches.is_present("add") {
         for entry in arg_matches.values_of("add").unwrap() {
-            let path = Path::new(entry);
-            if path.is_file() {
-                addlist_vec.push(parse_add(entry));
-            }
-            if path.is_dir() {
-                addlist_vec.append(&mut walk_list(parse_add(entry)));
-            }
+            push_add_entry(&mut addlist_vec, parse_add(entry));
         }
     }
 
     addlist_vec
 }
 
+/// Function that adds an [AddList] entry to `addlist_vec`, first removing
+/// any existing entry whose archive path is `dst` itself or nested under it
+/// (`dst/...`), so a later entry at the same destination overrides an
+/// earlier one instead of producing a duplicate archive path.
+fn push_add_entry(addlist_vec: &mut Vec<AddList>, addlist: AddList) {
+    let prefix = format!("{}/", addlist.dst);
+    addlist_vec.retain(|pair| pair.dst != addlist.dst && !pair.dst.starts_with(&prefix));
+
+    if addlist.src.is_file() {
+        addlist_vec.push(addlist);
+    } else if addlist.src.is_dir() {
+        addlist_vec.append(&mut walk_list(addlist));
+    }
+}
+
+#[cfg(test)]
+mod push_add_entry_tests {
+    use super::*;
+
+    fn addlist_for(src: &Path, dst: &str) -> AddList {
+        AddList {
+            src: src.to_path_buf(),
+            dst: String::from(dst),
+        }
+    }
+
+    #[test]
+    fn later_entry_overrides_earlier_entry_at_same_dst() {
+        let tmp = std::env::temp_dir().join(format!(
+            "vita-pack-vpk-test-{}-{}",
+            std::process::id(),
+            "push-override"
+        ));
+        std::fs::write(&tmp, b"data").unwrap();
+
+        let mut addlist_vec = Vec::new();
+        push_add_entry(&mut addlist_vec, addlist_for(&tmp, "eboot.bin"));
+        push_add_entry(&mut addlist_vec, addlist_for(&tmp, "eboot.bin"));
+
+        assert_eq!(addlist_vec.len(), 1);
+
+        std::fs::remove_file(&tmp).unwrap();
+    }
+
+    #[test]
+    fn directory_entry_overrides_nested_entries_under_its_dst() {
+        let tmp = std::env::temp_dir().join(format!(
+            "vita-pack-vpk-test-{}-{}",
+            std::process::id(),
+            "push-nested"
+        ));
+        std::fs::create_dir_all(&tmp).unwrap();
+        std::fs::write(tmp.join("a.txt"), b"a").unwrap();
+
+        let mut addlist_vec = Vec::new();
+        push_add_entry(&mut addlist_vec, addlist_for(&tmp.join("a.txt"), "assets/a.txt"));
+        assert_eq!(addlist_vec.len(), 1);
+
+        push_add_entry(&mut addlist_vec, addlist_for(&tmp, "assets"));
+        assert_eq!(addlist_vec.len(), 1);
+        assert_eq!(addlist_vec[0].dst, "assets/a.txt");
+
+        std::fs::remove_dir_all(&tmp).unwrap();
+    }
+}
+
+/// Function that resolves a required path option: the CLI flag `name` if
+/// explicitly given, otherwise `manifest_value`, exiting the process if
+/// neither is set. Since clap's `check_file` validator only runs on the CLI
+/// flag, a `manifest_value` is checked here the same way.
+fn resolve_required_path(
+    arg_matches: &ArgMatches,
+    name: &str,
+    manifest_value: Option<&PathBuf>,
+) -> PathBuf {
+    if arg_matches.occurrences_of(name) > 0 {
+        return PathBuf::from(arg_matches.value_of(name).unwrap());
+    }
+    if let Some(path) = manifest_value {
+        if let Err(error) = check_file(path.to_str().unwrap_or_default()) {
+            println!("error: Invalid manifest \"{}\" field: {}", name, error);
+            std::process::exit(exitcode::CONFIG);
+        }
+        return path.clone();
+    }
+    println!(
+        "error: Missing required --{} option (or manifest \"{}\" field)",
+        name, name
+    );
+    std::process::exit(exitcode::USAGE);
+}
+
+/// Function that will recursively walk the `src` directory of an [AddList]
+/// and build one [AddList] per file found, mapping each file's path relative
+/// to `src` onto the `dst` prefix.
+///
+/// Symlinks are followed so symlinked assets get packed; `WalkDir` detects
+/// the loops this can introduce and reports them as an error, which
+/// `filter_map(|e| e.ok())` then skips. Directory entries are skipped, and
+/// the relative path is normalized to forward slashes since VPKs are zip
+/// archives.
+///
+/// Returns a [Vec]<[AddList]>, one entry per real file under `src`.
 fn walk_list(addlist: AddList) -> Vec<AddList> {
-    let mut addlist_vec: Vec<AddList>;
+    let mut addlist_vec: Vec<AddList> = Vec::new();
+    let mut files: Vec<PathBuf> = Vec::new();
+
+    for entry in WalkDir::new(&addlist.src)
+        .follow_links(true)
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        if entry.file_type().is_file() {
+            files.push(entry.into_path());
+        }
+    }
 
-    for entry in WalkDir::new(addlist.src).into_iter().filter_map(|e| e.ok()) {
-        let path = entry.path();
-        let name = path.strip_prefix(Path::new(addlist.dst)).unwrap();
+    for file in files {
+        let relative = file
+            .strip_prefix(&addlist.src)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .replace('\\', "/");
+        let dst = format!("{}/{}", addlist.dst, relative);
+        addlist_vec.push(AddList { src: file, dst });
     }
 
     addlist_vec
 }
 
+#[cfg(test)]
+mod walk_list_tests {
+    use super::*;
+
+    #[test]
+    fn maps_nested_files_with_forward_slash_dst() {
+        let tmp = std::env::temp_dir().join(format!(
+            "vita-pack-vpk-test-{}-{}",
+            std::process::id(),
+            "walk-nested"
+        ));
+        let nested = tmp.join("sub");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(tmp.join("a.txt"), b"a").unwrap();
+        std::fs::write(nested.join("b.txt"), b"b").unwrap();
+
+        let mut result = walk_list(AddList {
+            src: tmp.clone(),
+            dst: String::from("assets"),
+        });
+        result.sort_by(|a, b| a.dst.cmp(&b.dst));
+
+        let dsts: Vec<&str> = result.iter().map(|pair| pair.dst.as_str()).collect();
+        assert_eq!(dsts, vec!["assets/a.txt", "assets/sub/b.txt"]);
+
+        std::fs::remove_dir_all(&tmp).unwrap();
+    }
+
+    #[test]
+    fn follows_symlinked_files() {
+        let tmp = std::env::temp_dir().join(format!(
+            "vita-pack-vpk-test-{}-{}",
+            std::process::id(),
+            "walk-symlink"
+        ));
+        let target_dir = tmp.join("real");
+        std::fs::create_dir_all(&target_dir).unwrap();
+        std::fs::write(target_dir.join("file.txt"), b"data").unwrap();
+
+        let link_dir = tmp.join("linked");
+        std::fs::create_dir_all(&tmp).unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&target_dir, &link_dir).unwrap();
+        #[cfg(windows)]
+        std::os::windows::fs::symlink_dir(&target_dir, &link_dir).unwrap();
+
+        let result = walk_list(AddList {
+            src: link_dir.clone(),
+            dst: String::from("assets"),
+        });
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].dst, "assets/file.txt");
+
+        std::fs::remove_dir_all(&tmp).unwrap();
+    }
+}
+
 /// Function that will make an [AddList] struct from the source [Path] and
 /// destination path as [String]
 ///
@@ -227,34 +624,324 @@ fn make_file(file_path: &Path) -> File {
     }
 }
 
+/// Progress message sent by a packing worker thread to the main thread while
+/// an [AddList] entry is being compressed.
+enum Message {
+    /// A worker started compressing the entry at this archive path
+    Started(String),
+    /// A worker finished compressing the entry, with its uncompressed byte size
+    Finished(String, u64),
+    /// A worker failed to read or compress the entry, with the error description
+    Error(String, String),
+}
+
+/// An [AddList] entry that has already been compressed into a standalone
+/// single-entry zip by a worker thread, ready to be spliced into the final
+/// archive on the main thread via [ZipWriter::raw_copy_file].
+struct PackedEntry {
+    zip_bytes: Vec<u8>,
+}
+
+/// Reads `pair.src` from disk and compresses it into a standalone,
+/// single-entry in-memory zip using `compression`.
+///
+/// Returns the uncompressed size alongside the serialized zip bytes, so the
+/// caller can splice the already-compressed entry into the final archive
+/// with [ZipWriter::raw_copy_file] without recompressing it.
+fn compress_entry(
+    pair: &AddList,
+    compression: (zip::CompressionMethod, Option<i32>),
+) -> zip::result::ZipResult<(u64, Vec<u8>)> {
+    use std::io::{Cursor, Write};
+    use zip::{write::FileOptions, ZipWriter};
+
+    let data = std::fs::read(&pair.src)?;
+    let options = FileOptions::default()
+        .compression_method(compression.0)
+        .compression_level(compression.1)
+        .unix_permissions(0o755);
+
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    writer.start_file(&pair.dst, options)?;
+    writer.write_all(&data)?;
+    Ok((data.len() as u64, writer.finish()?.into_inner()))
+}
+
 /// Function that will make the VPK archive with all the required files
 ///
+/// `compression` sets the [zip::CompressionMethod] and optional level applied
+/// to each added entry.
+///
+/// Entries are read from disk and compressed in parallel by a [rayon] worker
+/// pool, which reports progress back to the main thread over a [Message]
+/// channel. Since [ZipWriter] cannot be written to concurrently, each worker
+/// instead compresses its entry into its own standalone single-entry zip;
+/// the main thread then splices those already-compressed entries into the
+/// final archive sequentially via [ZipWriter::raw_copy_file], without
+/// recompressing them.
+///
 /// This is the final step of vita-pack-vpk. It returns nothing.
-fn pack_vpk(addlist: Vec<AddList>, vpk_path: &Path) -> zip::result::ZipResult<()> {
-    use std::io::prelude::*;
-    use zip::{write::FileOptions, CompressionMethod::Stored, ZipWriter};
+fn pack_vpk(
+    addlist: &[AddList],
+    vpk_path: &Path,
+    compression: (zip::CompressionMethod, Option<i32>),
+) -> zip::result::ZipResult<()> {
+    use rayon::prelude::*;
+    use std::io::Cursor;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{mpsc, Arc};
+    use zip::ZipWriter;
+
+    let (tx, rx) = mpsc::channel::<Message>();
+    let had_error = Arc::new(AtomicBool::new(false));
+
+    let entries: Vec<Option<PackedEntry>> = std::thread::scope(|scope| {
+        let had_error = had_error.clone();
+        let handle = scope.spawn(move || {
+            addlist
+                .iter()
+                .map(|pair| (pair, tx.clone(), had_error.clone()))
+                .collect::<Vec<_>>()
+                .into_par_iter()
+                .map(|(pair, tx, had_error)| {
+                    let _ = tx.send(Message::Started(pair.dst.clone()));
+                    match compress_entry(pair, compression) {
+                        Ok((size, zip_bytes)) => {
+                            let _ = tx.send(Message::Finished(pair.dst.clone(), size));
+                            Some(PackedEntry { zip_bytes })
+                        }
+                        Err(error) => {
+                            had_error.store(true, Ordering::SeqCst);
+                            let _ = tx.send(Message::Error(pair.dst.clone(), error.to_string()));
+                            None
+                        }
+                    }
+                })
+                .collect::<Vec<_>>()
+        });
+
+        for message in rx {
+            match message {
+                Message::Started(dst) => println!("[..] {}", dst),
+                Message::Finished(dst, size) => println!("[ok] {} ({})", dst, human_size(size)),
+                Message::Error(dst, error) => println!("[err] {}: {}", dst, error),
+            }
+        }
+
+        handle.join().expect("packing worker pool panicked")
+    });
+
+    if had_error.load(Ordering::SeqCst) {
+        return Err(zip::result::ZipError::Io(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            "one or more entries failed to read or compress",
+        )));
+    }
 
     // Variable that will manage ZipWriter (Zip Archive Generator) to write our
     // vpk file
     let mut vpk_writer: ZipWriter<File>;
-    let mut file_buff = Vec::new();
-    let options: FileOptions;
     // Variable that will allow to create and write to our new vpk file
     let vpk_file: File;
 
     vpk_file = make_file(vpk_path);
     vpk_writer = ZipWriter::new(vpk_file);
-    options = FileOptions::default()
-        .compression_method(Stored)
-        .unix_permissions(0o755);
 
-    for pair in addlist {
-        let mut file = File::open(&pair.src)?;
-        file.read_to_end(&mut file_buff)?;
-        vpk_writer.start_file(&pair.dst, options)?;
-        vpk_writer.write_all(&*file_buff)?;
-        file_buff.clear();
+    for entry in entries.into_iter().flatten() {
+        let mut archive = zip::ZipArchive::new(Cursor::new(entry.zip_bytes))?;
+        vpk_writer.raw_copy_file(archive.by_index(0)?)?;
     }
     vpk_writer.finish()?;
     Ok(())
+}
+
+/// Function that reopens the vpk written at `vpk_path` and validates that
+/// every entry of `addlist` is present exactly once with the right
+/// uncompressed size, in addition to the mandatory `eboot.bin` and
+/// `sce_sys/param.sfo` entries.
+///
+/// Prints any missing, duplicated or mismatched entry and exits non-zero via
+/// [exitcode] so CI pipelines can catch corrupt packs automatically.
+fn verify_vpk(addlist: &[AddList], vpk_path: &Path) {
+    let vpk_file = match File::open(vpk_path) {
+        Ok(file) => file,
+        Err(error) => {
+            println!("error: Unable to reopen the {:?} file : {:?}", vpk_path, error);
+            std::process::exit(exitcode::IOERR);
+        }
+    };
+
+    match check_vpk(addlist, vpk_file) {
+        Ok(()) => println!("Verification successful: vpk is valid"),
+        Err(problems) => {
+            for problem in problems {
+                println!("[ERR] {}", problem);
+            }
+            std::process::exit(exitcode::DATAERR);
+        }
+    }
+}
+
+/// Function that reads the entries of an already-opened vpk `reader` and
+/// checks them against `addlist`, without printing or exiting.
+///
+/// Returns `Ok(())` if every entry of `addlist` is present exactly once with
+/// the right uncompressed size, alongside the mandatory `eboot.bin` and
+/// `sce_sys/param.sfo` entries. Otherwise returns the list of problems found.
+fn check_vpk<R: std::io::Read + std::io::Seek>(
+    addlist: &[AddList],
+    reader: R,
+) -> Result<(), Vec<String>> {
+    let mut archive = match zip::ZipArchive::new(reader) {
+        Ok(archive) => archive,
+        Err(error) => return Err(vec![format!("Unable to read the archive: {:?}", error)]),
+    };
+
+    let mut seen: std::collections::HashMap<String, u32> = std::collections::HashMap::new();
+    for i in 0..archive.len() {
+        if let Ok(entry) = archive.by_index(i) {
+            *seen.entry(entry.name().to_string()).or_insert(0) += 1;
+        }
+    }
+
+    let mut problems = Vec::new();
+
+    for required in [DEFAULT_SFO_VPK_PATH, DEFAULT_EBOOT_VPK_PATH] {
+        if !seen.contains_key(required) {
+            problems.push(format!("Missing required entry: {}", required));
+        }
+    }
+
+    for pair in addlist {
+        match seen.get(&pair.dst) {
+            None => problems.push(format!("Missing entry: {}", pair.dst)),
+            Some(count) if *count > 1 => {
+                problems.push(format!("Duplicated entry: {} ({} times)", pair.dst, count));
+            }
+            Some(_) => {
+                let expected = match std::fs::metadata(&pair.src) {
+                    Ok(metadata) => metadata.len(),
+                    Err(_) => continue,
+                };
+                match archive.by_name(&pair.dst) {
+                    Ok(entry) if entry.size() != expected => {
+                        problems.push(format!(
+                            "Size mismatch for {}: expected {}, found {}",
+                            pair.dst,
+                            expected,
+                            entry.size()
+                        ));
+                    }
+                    Ok(_) => {}
+                    Err(error) => {
+                        problems.push(format!("Unable to read entry {}: {:?}", pair.dst, error));
+                    }
+                }
+            }
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems)
+    }
+}
+
+#[cfg(test)]
+mod check_vpk_tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+
+    fn write_test_vpk(entries: &[(&str, &[u8])]) -> Cursor<Vec<u8>> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = zip::write::FileOptions::default();
+        for (name, data) in entries {
+            writer.start_file(*name, options).unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap()
+    }
+
+    fn addlist_for(src: &Path, dst: &str) -> AddList {
+        AddList {
+            src: src.to_path_buf(),
+            dst: String::from(dst),
+        }
+    }
+
+    #[test]
+    fn accepts_a_complete_matching_archive() {
+        let tmp = std::env::temp_dir().join(format!(
+            "vita-pack-vpk-test-{}-{}",
+            std::process::id(),
+            "check-ok"
+        ));
+        std::fs::write(&tmp, b"eboot-data").unwrap();
+
+        let vpk = write_test_vpk(&[
+            (DEFAULT_SFO_VPK_PATH, b"sfo-data"),
+            (DEFAULT_EBOOT_VPK_PATH, b"eboot-data"),
+        ]);
+        let addlist = vec![addlist_for(&tmp, DEFAULT_EBOOT_VPK_PATH)];
+
+        assert_eq!(check_vpk(&addlist, vpk), Ok(()));
+
+        std::fs::remove_file(&tmp).unwrap();
+    }
+
+    #[test]
+    fn reports_missing_required_entries() {
+        let vpk = write_test_vpk(&[]);
+        assert_eq!(
+            check_vpk(&[], vpk),
+            Err(vec![
+                format!("Missing required entry: {}", DEFAULT_SFO_VPK_PATH),
+                format!("Missing required entry: {}", DEFAULT_EBOOT_VPK_PATH),
+            ])
+        );
+    }
+
+    #[test]
+    fn reports_duplicated_entries() {
+        let vpk = write_test_vpk(&[
+            (DEFAULT_SFO_VPK_PATH, b""),
+            (DEFAULT_EBOOT_VPK_PATH, b""),
+            ("assets/a.bin", b"1"),
+            ("assets/a.bin", b"2"),
+        ]);
+        let addlist = vec![addlist_for(Path::new("unused"), "assets/a.bin")];
+
+        let result = check_vpk(&addlist, vpk);
+        assert_eq!(
+            result,
+            Err(vec![String::from("Duplicated entry: assets/a.bin (2 times)")])
+        );
+    }
+
+    #[test]
+    fn reports_size_mismatch() {
+        let tmp = std::env::temp_dir().join(format!(
+            "vita-pack-vpk-test-{}-{}",
+            std::process::id(),
+            "check-mismatch"
+        ));
+        std::fs::write(&tmp, b"12345").unwrap();
+
+        let vpk = write_test_vpk(&[
+            (DEFAULT_SFO_VPK_PATH, b""),
+            (DEFAULT_EBOOT_VPK_PATH, b""),
+            ("assets/a.bin", b"123"),
+        ]);
+        let addlist = vec![addlist_for(&tmp, "assets/a.bin")];
+
+        assert_eq!(
+            check_vpk(&addlist, vpk),
+            Err(vec![String::from(
+                "Size mismatch for assets/a.bin: expected 5, found 3"
+            )])
+        );
+
+        std::fs::remove_file(&tmp).unwrap();
+    }
 }
\ No newline at end of file